@@ -1,8 +1,13 @@
+use std::collections::HashMap;
 use std::io::{self, Write};
 
+use num_complex::Complex64;
+
 #[derive(Debug, PartialEq, Clone)]
 enum Token {
     Number(f64),
+    Imaginary(f64),
+    Ident(String),
     Plus,
     Minus,
     Star,
@@ -10,196 +15,673 @@ enum Token {
     Caret,
     OpenParent,
     CloseParent,
+    Comma,
+    Equals,
+    Separator,
     Eof
 }
 
-fn tokenize(input: &str) -> Vec<Token> {
-    let mut tokens = Vec::new();
+fn describe_token(token: &Token) -> String {
+    match token {
+        Token::Number(n) => format!("'{}'", n),
+        Token::Imaginary(n) => format!("'{}i'", n),
+        Token::Ident(name) => format!("'{}'", name),
+        Token::Plus => "'+'".to_string(),
+        Token::Minus => "'-'".to_string(),
+        Token::Star => "'*'".to_string(),
+        Token::Slash => "'/'".to_string(),
+        Token::Caret => "'^'".to_string(),
+        Token::OpenParent => "'('".to_string(),
+        Token::CloseParent => "')'".to_string(),
+        Token::Comma => "','".to_string(),
+        Token::Equals => "'='".to_string(),
+        Token::Separator => "end of statement".to_string(),
+        Token::Eof => "end of input".to_string(),
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Span {
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug, PartialEq)]
+struct LexError {
+    span: Span,
+    message: String,
+}
+
+#[derive(Debug, PartialEq)]
+struct ParseError {
+    span: Span,
+    message: String,
+}
+
+fn scan_ident(chars: &mut std::iter::Peekable<std::str::Chars<'_>>, offset: &mut usize) -> String {
+    let mut s = String::new();
+    while let Some(&ch) = chars.peek() {
+        if ch.is_ascii_alphanumeric() || ch == '_' {
+            s.push(ch);
+            chars.next();
+            *offset += ch.len_utf8();
+        } else {
+            break;
+        }
+    }
+    s
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, Span)>, LexError> {
+    let mut tokens: Vec<(Token, Span)> = Vec::new();
     let mut chars = input.chars().peekable();
+    let mut offset = 0;
 
     while let Some(&c) = chars.peek() {
-        if let Some(last) = tokens.last() {
-            let prev_is_operand = matches!(last, Token::Number(_) | Token::CloseParent);
-            let curr_is_starting_operand = c == '(' || c.is_ascii_digit() || c == '.';
+        if let Some((last, _)) = tokens.last() {
+            let prev_is_operand = matches!(last, Token::Number(_) | Token::Imaginary(_) | Token::CloseParent);
+            let curr_is_starting_operand =
+                c == '(' || c.is_ascii_digit() || c == '.' || c.is_ascii_alphabetic() || c == '_';
             if prev_is_operand && curr_is_starting_operand {
-                tokens.push(Token::Star);
+                tokens.push((Token::Star, Span { start: offset, end: offset }));
             }
         }
 
         match c {
             '0'..='9' | '.' => {
+                let start = offset;
                 let mut s = String::new();
                 let mut has_dot = false;
                 while let Some(&ch) = chars.peek() {
                     if ch.is_ascii_digit() {
                         s.push(ch);
                         chars.next();
+                        offset += ch.len_utf8();
                     } else if ch == '.' && !has_dot {
                         has_dot = true;
                         s.push(ch);
                         chars.next();
+                        offset += ch.len_utf8();
                     } else {
                         break;
                     }
                 }
-                tokens.push(Token::Number(s.parse().unwrap()));
+                let value: f64 = s.parse().map_err(|_| LexError {
+                    span: Span { start, end: offset },
+                    message: format!("invalid number literal '{}'", s),
+                })?;
+                let trailing_start = offset;
+                let is_ident_start = matches!(chars.peek(), Some(&ch) if ch.is_ascii_alphabetic() || ch == '_');
+                if is_ident_start {
+                    let ident = scan_ident(&mut chars, &mut offset);
+                    if ident == "i" {
+                        tokens.push((Token::Imaginary(value), Span { start, end: offset }));
+                    } else {
+                        tokens.push((Token::Number(value), Span { start, end: trailing_start }));
+                        tokens.push((Token::Star, Span { start: trailing_start, end: trailing_start }));
+                        tokens.push((Token::Ident(ident), Span { start: trailing_start, end: offset }));
+                    }
+                } else {
+                    tokens.push((Token::Number(value), Span { start, end: offset }));
+                }
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let start = offset;
+                let ident = scan_ident(&mut chars, &mut offset);
+                if ident == "i" {
+                    tokens.push((Token::Imaginary(1.0), Span { start, end: offset }));
+                } else {
+                    tokens.push((Token::Ident(ident), Span { start, end: offset }));
+                }
+            }
+            '+' => { tokens.push((Token::Plus, Span { start: offset, end: offset + 1 })); chars.next(); offset += 1; }
+            '-' => { tokens.push((Token::Minus, Span { start: offset, end: offset + 1 })); chars.next(); offset += 1; }
+            '*' => { tokens.push((Token::Star, Span { start: offset, end: offset + 1 })); chars.next(); offset += 1; }
+            '/' => {
+                let mut lookahead = chars.clone();
+                lookahead.next();
+                if lookahead.peek() == Some(&'/') {
+                    while let Some(&ch) = chars.peek() {
+                        if ch == '\n' {
+                            break;
+                        }
+                        chars.next();
+                        offset += ch.len_utf8();
+                    }
+                } else {
+                    tokens.push((Token::Slash, Span { start: offset, end: offset + 1 }));
+                    chars.next();
+                    offset += 1;
+                }
+            }
+            '^' => { tokens.push((Token::Caret, Span { start: offset, end: offset + 1 })); chars.next(); offset += 1; }
+            '(' => { tokens.push((Token::OpenParent, Span { start: offset, end: offset + 1 })); chars.next(); offset += 1; }
+            ')' => { tokens.push((Token::CloseParent, Span { start: offset, end: offset + 1 })); chars.next(); offset += 1; }
+            ',' => { tokens.push((Token::Comma, Span { start: offset, end: offset + 1 })); chars.next(); offset += 1; }
+            '=' => { tokens.push((Token::Equals, Span { start: offset, end: offset + 1 })); chars.next(); offset += 1; }
+            ';' => { tokens.push((Token::Separator, Span { start: offset, end: offset + 1 })); chars.next(); offset += 1; }
+            '\n' => { tokens.push((Token::Separator, Span { start: offset, end: offset + 1 })); chars.next(); offset += 1; }
+            '#' => {
+                while let Some(&ch) = chars.peek() {
+                    if ch == '\n' {
+                        break;
+                    }
+                    chars.next();
+                    offset += ch.len_utf8();
+                }
+            }
+            ' ' | '\t' | '\r' => { chars.next(); offset += c.len_utf8(); }
+            other => {
+                return Err(LexError {
+                    span: Span { start: offset, end: offset + other.len_utf8() },
+                    message: format!("unexpected character '{}'", other),
+                });
             }
-            '+' => { tokens.push(Token::Plus); chars.next(); }
-            '-' => { tokens.push(Token::Minus); chars.next(); }
-            '*' => { tokens.push(Token::Star); chars.next(); }
-            '/' => { tokens.push(Token::Slash); chars.next(); }
-            '^' => { tokens.push(Token::Caret); chars.next(); }
-            '(' => { tokens.push(Token::OpenParent); chars.next(); }
-            ')' => { tokens.push(Token::CloseParent); chars.next(); }
-            ' ' | '\t' | '\n' | '\r' => { chars.next(); }
-            _ => { chars.next(); }
         }
     }
-    tokens.push(Token::Eof);
-    tokens
+    tokens.push((Token::Eof, Span { start: offset, end: offset }));
+    Ok(tokens)
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Neg,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Num(Complex64),
+    Var(String, Span),
+    Call(String, Vec<Expr>, Span),
+    BinOp(Op, Box<Expr>, Box<Expr>),
+    Unary(Op, Box<Expr>),
+}
+
+#[derive(Debug)]
+enum Statement {
+    Assign(String, Expr),
+    Value(Expr),
 }
 
 struct Parser {
-    tokens: Vec<Token>,
+    tokens: Vec<(Token, Span)>,
     pos: usize,
 }
 
 impl Parser {
-    fn new(tokens: Vec<Token>) -> Self {
+    fn new(tokens: Vec<(Token, Span)>) -> Self {
         Self { tokens, pos: 0 }
     }
 
     fn curr(&self) -> &Token {
-        &self.tokens[self.pos]
+        &self.tokens[self.pos].0
+    }
+
+    fn curr_span(&self) -> Span {
+        self.tokens[self.pos].1
     }
 
     fn consume(&mut self) {
         self.pos += 1;
     }
 
-    fn parse(&mut self) -> f64 {
-        self.expr()
+    fn parse(&mut self) -> Result<Statement, ParseError> {
+        if let Token::Ident(name) = self.curr().clone() {
+            if self.tokens[self.pos + 1].0 == Token::Equals {
+                let name_span = self.curr_span();
+                if lookup_constant(&name).is_some() {
+                    return Err(ParseError {
+                        span: name_span,
+                        message: format!("cannot assign to constant '{}'", name),
+                    });
+                }
+                self.consume();
+                self.consume();
+                let expr = self.expr()?;
+                self.expect_eof()?;
+                return Ok(Statement::Assign(name, expr));
+            }
+        }
+
+        let expr = self.expr()?;
+        self.expect_eof()?;
+        Ok(Statement::Value(expr))
     }
 
-    fn expr(&mut self) -> f64 {
-        let mut left = self.term();
+    fn expect_eof(&self) -> Result<(), ParseError> {
+        if *self.curr() != Token::Eof {
+            return Err(ParseError {
+                span: self.curr_span(),
+                message: format!("unexpected trailing token {}", describe_token(self.curr())),
+            });
+        }
+        Ok(())
+    }
+
+    fn expr(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.term()?;
         loop {
-            let current_token = self.curr();
-            if matches!(current_token, Token::Plus | Token::Minus) {
-                let op = current_token.clone();
+            let op = self.curr().clone();
+            if matches!(op, Token::Plus | Token::Minus) {
                 self.consume();
-                let right = self.term();
-
-                left = match op {
-                    Token::Plus => left + right,
-                    Token::Minus => left - right,
-                    _ => unreachable!(),
-                };
+                let right = self.term()?;
+                let op = if op == Token::Plus { Op::Add } else { Op::Sub };
+                left = Expr::BinOp(op, Box::new(left), Box::new(right));
             } else {
                 break;
             }
         }
-        left
+        Ok(left)
     }
-    
-    fn term(&mut self) -> f64 {
-        let mut left = self.factor();
+
+    fn term(&mut self) -> Result<Expr, ParseError> {
+        let mut left = self.factor()?;
         loop {
-            let current_token = self.curr();
-            if matches!(current_token, Token::Star | Token::Slash) {
-                let op = current_token.clone();
+            let op = self.curr().clone();
+            if matches!(op, Token::Star | Token::Slash) {
                 self.consume();
-                let right = self.factor();
-
-                left = match op {
-                    Token::Star => left * right,
-                    Token::Slash => left / right,
-                    _ => unreachable!(),
-                };
+                let right = self.factor()?;
+                let op = if op == Token::Star { Op::Mul } else { Op::Div };
+                left = Expr::BinOp(op, Box::new(left), Box::new(right));
             } else {
                 break;
             }
         }
-        left
+        Ok(left)
     }
-    
-    fn factor(&mut self) -> f64 {
-        let left = self.unary();
+
+    fn factor(&mut self) -> Result<Expr, ParseError> {
+        let left = self.unary()?;
         if let Token::Caret = self.curr() {
             self.consume();
-            let right = self.factor(); 
-            left.powf(right)
+            let right = self.factor()?;
+            Ok(Expr::BinOp(Op::Pow, Box::new(left), Box::new(right)))
         } else {
-            left
+            Ok(left)
         }
     }
 
-    fn unary(&mut self) -> f64 {
-        let mut sign = 1.0;
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        let mut negate = false;
         loop {
             let current_token = self.curr();
             if matches!(current_token, Token::Plus | Token::Minus) {
                 if let Token::Minus = current_token {
-                    sign = -sign;
+                    negate = !negate;
                 }
-                self.consume(); 
+                self.consume();
             } else {
                 break;
             }
         }
-        sign * self.primary()
+        let operand = self.primary()?;
+        Ok(if negate {
+            Expr::Unary(Op::Neg, Box::new(operand))
+        } else {
+            operand
+        })
     }
 
-    fn primary(&mut self) -> f64 {
+    fn primary(&mut self) -> Result<Expr, ParseError> {
         match self.curr() {
             Token::Number(n) => {
-                let val = *n; 
-                self.consume(); 
-                val
+                let val = Expr::Num(Complex64::new(*n, 0.0));
+                self.consume();
+                Ok(val)
+            }
+            Token::Imaginary(n) => {
+                let val = Expr::Num(Complex64::new(0.0, *n));
+                self.consume();
+                Ok(val)
             }
             Token::OpenParent => {
-                self.consume(); 
-                let val = self.expr(); 
-                self.consume(); 
-                val
+                self.consume();
+                let val = self.expr()?;
+                if *self.curr() != Token::CloseParent {
+                    return Err(ParseError {
+                        span: self.curr_span(),
+                        message: "expected ')'".to_string(),
+                    });
+                }
+                self.consume();
+                Ok(val)
             }
-            _ => {
-                panic!("{:?}", self.curr());
+            Token::Ident(_) => self.ident_or_call(),
+            other => Err(ParseError {
+                span: self.curr_span(),
+                message: format!("expected number or '(', found {}", describe_token(other)),
+            }),
+        }
+    }
+
+    fn ident_or_call(&mut self) -> Result<Expr, ParseError> {
+        let name = match self.curr() {
+            Token::Ident(name) => name.clone(),
+            _ => unreachable!(),
+        };
+        let name_span = self.curr_span();
+        self.consume();
+
+        if *self.curr() == Token::OpenParent {
+            self.consume();
+            let mut args = Vec::new();
+            if *self.curr() != Token::CloseParent {
+                loop {
+                    args.push(self.expr()?);
+                    if *self.curr() == Token::Comma {
+                        self.consume();
+                    } else {
+                        break;
+                    }
+                }
             }
+            if *self.curr() != Token::CloseParent {
+                return Err(ParseError {
+                    span: self.curr_span(),
+                    message: "expected ')'".to_string(),
+                });
+            }
+            self.consume();
+            Ok(Expr::Call(name, args, name_span))
+        } else {
+            Ok(Expr::Var(name, name_span))
         }
     }
 }
 
-fn main() {
+#[derive(Debug, PartialEq)]
+struct EvalError {
+    span: Span,
+    message: String,
+}
+
+fn eval(expr: &Expr, env: &HashMap<String, Complex64>) -> Result<Complex64, EvalError> {
+    match expr {
+        Expr::Num(n) => Ok(*n),
+        Expr::Var(name, span) => lookup_constant(name)
+            .or_else(|| env.get(name).copied())
+            .ok_or_else(|| EvalError {
+                span: *span,
+                message: format!("undefined variable '{}'", name),
+            }),
+        Expr::Call(name, args, span) => {
+            let values = args.iter().map(|a| eval(a, env)).collect::<Result<Vec<_>, _>>()?;
+            call_function(name, &values).map_err(|message| EvalError { span: *span, message })
+        }
+        Expr::BinOp(op, left, right) => {
+            let left = eval(left, env)?;
+            let right = eval(right, env)?;
+            Ok(match op {
+                Op::Add => left + right,
+                Op::Sub => left - right,
+                Op::Mul => left * right,
+                Op::Div => left / right,
+                Op::Pow => left.powc(right),
+                Op::Neg => unreachable!("Neg is only produced as a unary operator"),
+            })
+        }
+        Expr::Unary(op, operand) => {
+            let value = eval(operand, env)?;
+            Ok(match op {
+                Op::Neg => negate(value),
+                _ => unreachable!("only Neg is produced as a unary operator"),
+            })
+        }
+    }
+}
+
+/// Negates a complex value without turning an exact-zero component into
+/// `-0.0`, which would otherwise flip the branch cut of `sqrt`/`powc`
+/// (e.g. `sqrt(-4)` landing on `-2i` instead of the principal `2i`).
+fn negate(z: Complex64) -> Complex64 {
+    fn flip(x: f64) -> f64 {
+        if x == 0.0 {
+            0.0
+        } else {
+            -x
+        }
+    }
+    Complex64::new(flip(z.re), flip(z.im))
+}
 
-    loop {
-        io::stdout().flush().unwrap();
+fn split_statements(tokens: Vec<(Token, Span)>) -> Vec<Vec<(Token, Span)>> {
+    let mut statements = Vec::new();
+    let mut current: Vec<(Token, Span)> = Vec::new();
+    for (token, span) in tokens {
+        match token {
+            Token::Separator | Token::Eof => {
+                if !current.is_empty() {
+                    current.push((Token::Eof, span));
+                    statements.push(std::mem::take(&mut current));
+                }
+            }
+            other => current.push((other, span)),
+        }
+    }
+    statements
+}
 
-        let mut expr = String::new();
-        io::stdin().read_line(&mut expr).unwrap();
-        let expr = expr.trim();
+fn lookup_constant(name: &str) -> Option<Complex64> {
+    let value = match name {
+        "pi" => std::f64::consts::PI,
+        "e" => std::f64::consts::E,
+        "tau" => std::f64::consts::TAU,
+        _ => return None,
+    };
+    Some(Complex64::new(value, 0.0))
+}
 
-        let tokens = tokenize(expr);
-        if tokens.len() == 1 && matches!(tokens[0], Token::Eof) {
-            continue;
+fn call_function(name: &str, args: &[Complex64]) -> Result<Complex64, String> {
+    fn expect(name: &str, args: &[Complex64], n: usize) -> Result<(), String> {
+        if args.len() != n {
+            Err(format!("'{}' expects {} argument(s), got {}", name, n, args.len()))
+        } else {
+            Ok(())
         }
+    }
 
-        let mut parser = Parser::new(tokens);
-        let result = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| parser.parse())) {
-            Ok(val) => val,
-            Err(_) => {
+    match name {
+        "sin" => { expect(name, args, 1)?; Ok(args[0].sin()) }
+        "cos" => { expect(name, args, 1)?; Ok(args[0].cos()) }
+        "tan" => { expect(name, args, 1)?; Ok(args[0].tan()) }
+        "sqrt" => { expect(name, args, 1)?; Ok(args[0].sqrt()) }
+        "ln" => { expect(name, args, 1)?; Ok(args[0].ln()) }
+        "log" => {
+            expect(name, args, 2)?;
+            Ok(args[0].ln() / args[1].ln())
+        }
+        "abs" => { expect(name, args, 1)?; Ok(Complex64::new(args[0].norm(), 0.0)) }
+        "floor" => { expect(name, args, 1)?; Ok(Complex64::new(args[0].re.floor(), 0.0)) }
+        "min" => { expect(name, args, 2)?; Ok(Complex64::new(args[0].re.min(args[1].re), 0.0)) }
+        "max" => { expect(name, args, 2)?; Ok(Complex64::new(args[0].re.max(args[1].re), 0.0)) }
+        _ => Err(format!("unknown function '{}'", name)),
+    }
+}
+
+fn format_complex(z: Complex64, real_only: bool) -> String {
+    if real_only || z.im == 0.0 {
+        format!("{}", z.re)
+    } else if z.im > 0.0 {
+        format!("{}+{}i", z.re, z.im)
+    } else {
+        format!("{}-{}i", z.re, -z.im)
+    }
+}
+
+fn print_caret(source: &str, span: Span) {
+    let mut line_start = 0;
+    for line in source.split('\n') {
+        let line_end = line_start + line.len();
+        if span.start <= line_end {
+            let start = (span.start - line_start).min(line.len());
+            let end = (span.end - line_start).max(start + 1).min(line.len() + 1);
+            println!("{}", line);
+            let mut marker = String::new();
+            for _ in 0..start {
+                marker.push(' ');
+            }
+            for _ in start..end {
+                marker.push('^');
+            }
+            println!("{}", marker);
+            return;
+        }
+        line_start = line_end + 1;
+    }
+}
+
+fn run(source: &str, env: &mut HashMap<String, Complex64>, real_only: bool) {
+    let tokens = match tokenize(source) {
+        Ok(tokens) => tokens,
+        Err(err) => {
+            print_caret(source, err.span);
+            println!("{}", err.message);
+            return;
+        }
+    };
+
+    for statement_tokens in split_statements(tokens) {
+        let mut parser = Parser::new(statement_tokens);
+        let statement = match parser.parse() {
+            Ok(statement) => statement,
+            Err(err) => {
+                print_caret(source, err.span);
+                println!("{}", err.message);
                 continue;
             }
         };
 
-        println!("Result: {}", result);
+        let (name, rhs) = match statement {
+            Statement::Assign(name, rhs) => (Some(name), rhs),
+            Statement::Value(rhs) => (None, rhs),
+        };
+        let value = match eval(&rhs, env) {
+            Ok(value) => value,
+            Err(err) => {
+                print_caret(source, err.span);
+                println!("{}", err.message);
+                continue;
+            }
+        };
 
-        io::stdout().flush().unwrap();
+        match name {
+            Some(name) => {
+                env.insert(name.clone(), value);
+                println!("{} = {}", name, format_complex(value, real_only));
+            }
+            None => println!("Result: {}", format_complex(value, real_only)),
+        }
+    }
+}
 
-        let mut choice = String::new();
-        io::stdin().read_line(&mut choice).unwrap();
-        if choice.trim().to_lowercase() == "n" {
-            break;
+fn main() {
+    let mut real_only = false;
+    let mut path = None;
+    for arg in std::env::args().skip(1) {
+        if arg == "--real" {
+            real_only = true;
+        } else {
+            path = Some(arg);
+        }
+    }
+
+    let mut env: HashMap<String, Complex64> = HashMap::new();
+
+    if let Some(path) = path {
+        let source = std::fs::read_to_string(&path).unwrap_or_else(|err| {
+            eprintln!("failed to read {}: {}", path, err);
+            std::process::exit(1);
+        });
+        run(&source, &mut env, real_only);
+    } else {
+        loop {
+            io::stdout().flush().unwrap();
+            let mut line = String::new();
+            if io::stdin().read_line(&mut line).unwrap() == 0 {
+                break;
+            }
+            run(&line, &mut env, real_only);
+        }
+    }
+
+    io::stdout().flush().unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval_str(src: &str) -> Complex64 {
+        eval_str_with_env(src, &mut HashMap::new())
+    }
+
+    fn eval_str_with_env(src: &str, env: &mut HashMap<String, Complex64>) -> Complex64 {
+        let tokens = tokenize(src).unwrap();
+        let statement = Parser::new(tokens).parse().unwrap();
+        let (name, rhs) = match statement {
+            Statement::Assign(name, rhs) => (Some(name), rhs),
+            Statement::Value(rhs) => (None, rhs),
+        };
+        let value = eval(&rhs, env).unwrap();
+        if let Some(name) = name {
+            env.insert(name, value);
         }
+        value
+    }
+
+    #[test]
+    fn sqrt_of_negative_real_takes_principal_branch() {
+        let z = eval_str("sqrt(-4)");
+        assert!((z.re - 0.0).abs() < 1e-9);
+        assert!((z.im - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn pow_one_half_of_negative_real_takes_principal_branch() {
+        let z = eval_str("(-1) ^ 0.5");
+        assert!(z.re.abs() < 1e-9);
+        assert!((z.im - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn assigning_to_a_constant_name_is_rejected() {
+        let tokens = tokenize("pi = 5").unwrap();
+        let err = Parser::new(tokens).parse().unwrap_err();
+        assert_eq!(err.message, "cannot assign to constant 'pi'");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn variables_persist_across_statements() {
+        let mut env = HashMap::new();
+        eval_str_with_env("x = 2", &mut env);
+        let z = eval_str_with_env("x + 3", &mut env);
+        assert_eq!(z, Complex64::new(5.0, 0.0));
+    }
+
+    #[test]
+    fn tokenize_inserts_implicit_multiplication() {
+        let tokens = tokenize("2pi").unwrap();
+        let kinds: Vec<&Token> = tokens.iter().map(|(t, _)| t).collect();
+        assert!(matches!(kinds[0], Token::Number(n) if *n == 2.0));
+        assert_eq!(*kinds[1], Token::Star);
+        assert!(matches!(kinds[2], Token::Ident(name) if name == "pi"));
+    }
+
+    #[test]
+    fn unexpected_trailing_token_reports_surface_syntax() {
+        let tokens = tokenize("1)").unwrap();
+        let err = Parser::new(tokens).parse().unwrap_err();
+        assert_eq!(err.message, "unexpected trailing token ')'");
+    }
+
+    #[test]
+    fn call_function_checks_arity() {
+        let err = call_function("sqrt", &[]).unwrap_err();
+        assert_eq!(err, "'sqrt' expects 1 argument(s), got 0");
+    }
+}